@@ -1,15 +1,24 @@
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
 use rand::{
     distributions::{Bernoulli, Distribution},
     Rng,
 };
-use std::fmt::Debug;
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::ToString};
 
 use crate::{
     elementset::*,
     error::{CountError, CountResult},
 };
 
+/// With the `serde` feature enabled, the full state can be serialized
+/// and deserialized to snapshot and resume a mid-stream estimate.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "E: crate::elementset::Serializable"))]
 pub struct StreamCountEstimator<E: ElementSet> {
     elements: E,
     capacity: usize,
@@ -31,6 +40,53 @@ fn in_unit_interval(input: f64) -> CountResult<()> {
     Ok(())
 }
 
+/// Throws an error if `value` is not strictly positive, with `name` used in
+/// the error message.
+fn positive(value: usize, name: &str) -> CountResult<()> {
+    if value == 0 {
+        return Err(CountError::WrongInitialization(format!(
+            "{name} must be positive."
+        )));
+    }
+    Ok(())
+}
+
+/// Inverts the `epsilon`-`delta` bound used by [`StreamCountEstimator::new`]
+/// to report the `epsilon` a fixed `capacity` achieves for a target `delta`.
+/// Returns `(epsilon, delta)`, the latter echoed back for convenience.
+pub fn guarantee(capacity: usize, delta: f64, stream_length: usize) -> CountResult<(f64, f64)> {
+    in_unit_interval(delta)?;
+    positive(capacity, "capacity")?;
+    positive(stream_length, "stream_length")?;
+    if delta == 0.0 {
+        return Err(CountError::WrongInitialization(
+            "delta must be positive.".to_string(),
+        ));
+    }
+    let epsilon = (12.0 / capacity as f64 * (8.0 * stream_length as f64 / delta).log2()).sqrt();
+    Ok((epsilon, delta))
+}
+
+/// Inverts the `epsilon`-`delta` bound used by [`StreamCountEstimator::new`]
+/// to compute the `capacity` required to guarantee a given `epsilon` and
+/// `delta` over a stream of length `stream_length`.
+pub fn required_capacity(epsilon: f64, delta: f64, stream_length: usize) -> CountResult<usize> {
+    in_unit_interval(epsilon)?;
+    in_unit_interval(delta)?;
+    positive(stream_length, "stream_length")?;
+    if epsilon == 0.0 {
+        return Err(CountError::WrongInitialization(
+            "epsilon must be positive.".to_string(),
+        ));
+    }
+    if delta == 0.0 {
+        return Err(CountError::WrongInitialization(
+            "delta must be positive.".to_string(),
+        ));
+    }
+    Ok((12.0 / epsilon.powi(2) * (8.0 * (stream_length as f64) / delta).log2()).ceil() as usize)
+}
+
 impl<E> StreamCountEstimator<E>
 where
     E: ElementSet,
@@ -39,11 +95,9 @@ where
     /// Creates a new StreamCountEstimator, giving an `epsilon`-`delta` approximation
     /// for a data stream of length `stream_length`.
     /// The internal space capacity is calculated to guarantee the approximation goodness.
+    /// Returns `CountError::WrongInitialization` if `stream_length` is `0`.
     pub fn new(epsilon: f64, delta: f64, stream_length: usize) -> CountResult<Self> {
-        in_unit_interval(epsilon)?;
-        in_unit_interval(delta)?;
-        let capacity = (12.0 / epsilon.powi(2) * (8.0 * (stream_length as f64) / delta).log2())
-            .ceil() as usize;
+        let capacity = required_capacity(epsilon, delta, stream_length)?;
         Ok(StreamCountEstimator {
             elements: ElementSet::with_capacity(capacity),
             capacity,
@@ -61,6 +115,10 @@ where
         })
     }
 
+    /// Requires the `std` feature (uses `rand::thread_rng`); see
+    /// [`estimate_distinct_elements_iter_with_rng`](Self::estimate_distinct_elements_iter_with_rng)
+    /// for a `no_std`-compatible equivalent.
+    #[cfg(feature = "std")]
     pub fn estimate_distinct_elements_iter(
         &mut self,
         it: impl Iterator<Item = E::Element>,
@@ -82,6 +140,152 @@ where
         Ok(self.elements.len() * self.sampling_round)
     }
 
+    /// Merges `other` into `self`, combining estimators built over
+    /// disjoint shards of one stream. Both must share the same `capacity`.
+    /// Requires the `std` feature (uses `rand::thread_rng`).
+    #[cfg(feature = "std")]
+    pub fn merge(&mut self, other: StreamCountEstimator<E>) -> CountResult<()> {
+        self.merge_with_rng(other, &mut rand::thread_rng())
+    }
+
+    /// Owning variant of [`merge`](Self::merge) that consumes both
+    /// estimators and returns the merged result.
+    #[cfg(feature = "std")]
+    pub fn union(mut self, other: StreamCountEstimator<E>) -> CountResult<Self> {
+        self.merge(other)?;
+        Ok(self)
+    }
+
+    fn merge_with_rng<R: Rng + ?Sized>(
+        &mut self,
+        other: StreamCountEstimator<E>,
+        rng: &mut R,
+    ) -> CountResult<()> {
+        if self.capacity != other.capacity {
+            return Err(CountError::Message(format!(
+                "Cannot merge estimators with different capacities ({} and {}).",
+                self.capacity, other.capacity
+            )));
+        }
+
+        let target_round = self.sampling_round.max(other.sampling_round);
+        let self_elements = core::mem::replace(&mut self.elements, E::with_capacity(self.capacity));
+
+        let leveled_self = Self::subsample_to_round(
+            self_elements,
+            self.sampling_round,
+            target_round,
+            self.capacity,
+            rng,
+        )?;
+        let leveled_other = Self::subsample_to_round(
+            other.elements,
+            other.sampling_round,
+            target_round,
+            self.capacity,
+            rng,
+        )?;
+
+        let mut union = E::with_capacity(self.capacity);
+        for elem in leveled_self.iter().chain(leveled_other.iter()) {
+            union.insert(elem.clone());
+        }
+
+        let mut round = target_round;
+        while union.len() >= self.capacity {
+            let prob_dist =
+                Bernoulli::from_ratio(1, 2).map_err(|err| CountError::Message(err.to_string()))?;
+            let mut subsampled = E::with_capacity(self.capacity);
+            for elem in union.iter() {
+                if prob_dist.sample(rng) {
+                    subsampled.insert(elem.clone());
+                }
+            }
+            union = subsampled;
+            round *= 2;
+        }
+
+        self.elements = union;
+        self.sampling_round = round;
+        Ok(())
+    }
+
+    /// Subsamples `elements` from rate `1/round` down to rate
+    /// `1/target_round` via `log2(target_round/round)` Bernoulli(1/2) passes.
+    fn subsample_to_round<R: Rng + ?Sized>(
+        elements: E,
+        round: usize,
+        target_round: usize,
+        capacity: usize,
+        rng: &mut R,
+    ) -> CountResult<E> {
+        let passes = (target_round / round).trailing_zeros();
+        let mut current = elements;
+        for _ in 0..passes {
+            let prob_dist =
+                Bernoulli::from_ratio(1, 2).map_err(|err| CountError::Message(err.to_string()))?;
+            let mut next = E::with_capacity(capacity);
+            for elem in current.iter() {
+                if prob_dist.sample(rng) {
+                    next.insert(elem.clone());
+                }
+            }
+            current = next;
+        }
+        Ok(current)
+    }
+
+    /// Lazily processes `it`, yielding the running distinct-element estimate
+    /// after each consumed element, unlike [`estimate_distinct_elements_iter`](Self::estimate_distinct_elements_iter).
+    /// Requires the `std` feature (uses `rand::thread_rng`); see
+    /// [`estimate_stream_with_rng`](Self::estimate_stream_with_rng) for a
+    /// `no_std`-compatible equivalent.
+    #[cfg(feature = "std")]
+    pub fn estimate_stream<I>(self, it: I) -> impl Iterator<Item = CountResult<usize>>
+    where
+        I: IntoIterator<Item = E::Element>,
+    {
+        self.estimate_stream_with_rng(it, rand::thread_rng())
+    }
+
+    /// [`estimate_stream`](Self::estimate_stream) with an explicit `Rng`, for
+    /// reproducible estimates under a fixed seed.
+    pub fn estimate_stream_with_rng<I, R>(
+        mut self,
+        it: I,
+        mut rng: R,
+    ) -> impl Iterator<Item = CountResult<usize>>
+    where
+        I: IntoIterator<Item = E::Element>,
+        R: Rng,
+    {
+        let mut it = it.into_iter();
+        core::iter::from_fn(move || {
+            let elem = it.next()?;
+            loop {
+                match self.process_element_with_rng(elem.clone(), &mut rng) {
+                    Ok(Some(())) => break,
+                    Ok(None) => continue,
+                    Err(err) => return Some(Err(err)),
+                }
+            }
+            Some(Ok(self.elements.len() * self.sampling_round))
+        })
+    }
+
+    /// Returns the retained set, an (approximately) uniform sample of the
+    /// distinct elements seen so far at [`sample_inclusion_probability`](Self::sample_inclusion_probability).
+    pub fn sample_distinct_elements(&self) -> impl Iterator<Item = &E::Element> {
+        self.elements.iter()
+    }
+
+    /// The probability (`1 / sampling_round`) at which each element in
+    /// [`sample_distinct_elements`](Self::sample_distinct_elements) was retained.
+    pub fn sample_inclusion_probability(&self) -> f64 {
+        1.0 / self.sampling_round as f64
+    }
+
+    #[cfg(feature = "std")]
     fn process_element(&mut self, element: E::Element) -> CountResult<Option<()>> {
         self.process_element_with_rng(element, &mut rand::thread_rng())
     }
@@ -122,15 +326,36 @@ where
     }
 }
 
+#[cfg(feature = "heapless")]
+impl<T, const N: usize> StreamCountEstimator<heapless::Vec<T, N>>
+where
+    T: Clone + Eq,
+{
+    /// Creates a new StreamCountEstimator backed by a stack-allocated,
+    /// const-capacity element set, for `no_std` / allocation-free targets.
+    /// The capacity is `N`; the approximation goodness depends on it just
+    /// as with [`with_capacity`](Self::with_capacity). `N` is inferred from
+    /// the full `StreamCountEstimator<heapless::Vec<T, N>>` annotation, not
+    /// turbofished on the call.
+    pub fn with_const_capacity() -> CountResult<Self> {
+        Ok(StreamCountEstimator {
+            elements: heapless::Vec::new(),
+            capacity: N,
+            sampling_round: 1,
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::collections::HashSet;
 
     use insta::*;
     use itertools::Itertools;
+    use proptest::prelude::*;
     use rand::{rngs::StdRng, Rng, SeedableRng};
 
-    use super::StreamCountEstimator;
+    use super::{guarantee, required_capacity, StreamCountEstimator};
 
     #[test]
     fn incorrect_input_params() {
@@ -150,6 +375,49 @@ mod test {
         "###);
     }
 
+    #[test]
+    fn new_rejects_zero_stream_length() {
+        let err = StreamCountEstimator::<HashSet<u32>>::new(0.1, 0.1, 0)
+            .expect_err("Expected error.");
+        assert_snapshot!(err, @r###"
+        CountError(
+        	WrongInitializiation(stream_length must be positive.)
+        )
+        "###);
+    }
+
+    #[test]
+    fn required_capacity_matches_new() {
+        let capacity = required_capacity(0.1, 0.1, 1000).unwrap();
+        let scount = StreamCountEstimator::<HashSet<u32>>::new(0.1, 0.1, 1000).unwrap();
+
+        assert_eq!(capacity, scount.capacity);
+    }
+
+    #[test]
+    fn guarantee_inverts_required_capacity() {
+        let epsilon = 0.1;
+        let delta = 0.1;
+        let stream_length = 1000;
+        let capacity = required_capacity(epsilon, delta, stream_length).unwrap();
+
+        let (achieved_epsilon, achieved_delta) =
+            guarantee(capacity, delta, stream_length).unwrap();
+
+        assert_eq!(achieved_delta, delta);
+        assert!(achieved_epsilon <= epsilon);
+    }
+
+    #[test]
+    fn calibration_rejects_degenerate_inputs() {
+        assert!(required_capacity(0.0, 0.5, 1000).is_err());
+        assert!(required_capacity(0.5, 0.0, 1000).is_err());
+        assert!(required_capacity(0.5, 0.5, 0).is_err());
+        assert!(guarantee(0, 0.5, 1000).is_err());
+        assert!(guarantee(10, 0.0, 1000).is_err());
+        assert!(guarantee(10, 0.5, 0).is_err());
+    }
+
     #[test]
     fn process_element() {
         let mut scount = StreamCountEstimator::<Vec<usize>>::with_capacity(10).unwrap();
@@ -209,4 +477,154 @@ mod test {
 
         assert_eq!(count, 2);
     }
+
+    #[test]
+    fn merge_two_shards_respects_capacity_and_tolerance() {
+        // The two shards draw from disjoint value ranges, so a merge that
+        // silently drops one shard (e.g. a no-op returning `self` unchanged)
+        // is caught directly: the merged set would be missing every element
+        // from the dropped half, and the estimate would collapse to roughly
+        // one shard's solo count instead of the true union.
+        let mut source_rng = StdRng::seed_from_u64(7);
+        let first_half = (0..1000).map(|_| source_rng.gen_range(0..30)).collect_vec();
+        let second_half = (0..1000)
+            .map(|_| source_rng.gen_range(30..60))
+            .collect_vec();
+        let true_distinct = first_half
+            .iter()
+            .chain(second_half.iter())
+            .copied()
+            .collect::<HashSet<_>>()
+            .len();
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut shard_one = StreamCountEstimator::<Vec<i32>>::with_capacity(30).unwrap();
+        shard_one
+            .estimate_distinct_elements_iter_with_rng(first_half.iter().copied(), &mut rng)
+            .unwrap();
+        let mut shard_two = StreamCountEstimator::<Vec<i32>>::with_capacity(30).unwrap();
+        shard_two
+            .estimate_distinct_elements_iter_with_rng(second_half.iter().copied(), &mut rng)
+            .unwrap();
+
+        shard_one.merge_with_rng(shard_two, &mut rng).unwrap();
+
+        assert!(shard_one.elements.len() <= shard_one.capacity);
+        assert!(
+            shard_one.elements.iter().any(|&elem| elem < 30),
+            "merged set lost every element from the first shard"
+        );
+        assert!(
+            shard_one.elements.iter().any(|&elem| elem >= 30),
+            "merged set lost every element from the second shard"
+        );
+
+        let estimate = shard_one.elements.len() * shard_one.sampling_round;
+        let tolerance = (true_distinct as f64 * 0.25).ceil() as usize;
+        assert!(
+            estimate.abs_diff(true_distinct) <= tolerance,
+            "estimate {estimate} too far from true distinct count {true_distinct}"
+        );
+    }
+
+    #[test]
+    fn merge_rejects_mismatched_capacities() {
+        let one = StreamCountEstimator::<HashSet<i32>>::with_capacity(10).unwrap();
+        let other = StreamCountEstimator::<HashSet<i32>>::with_capacity(20).unwrap();
+
+        assert!(one.union(other).is_err());
+    }
+
+    #[test]
+    fn estimate_stream_last_value_matches_batch_result() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let input_vec = (0..1000).map(|_| rng.gen_range(0..15)).collect_vec();
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut scount = StreamCountEstimator::<Vec<i32>>::with_capacity(10).unwrap();
+        let expected = scount
+            .estimate_distinct_elements_iter_with_rng(input_vec.clone().into_iter(), &mut rng)
+            .unwrap();
+
+        let rng = StdRng::seed_from_u64(1);
+        let scount = StreamCountEstimator::<Vec<i32>>::with_capacity(10).unwrap();
+        let last = scount
+            .estimate_stream_with_rng(input_vec, rng)
+            .last()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(last, expected);
+    }
+
+    proptest! {
+        #[test]
+        fn sampled_elements_were_inserted(input_vec in prop::collection::vec(0..50i32, 0..500)) {
+            let seen: HashSet<i32> = input_vec.iter().copied().collect();
+
+            let mut rng = StdRng::seed_from_u64(99);
+            let mut scount = StreamCountEstimator::<HashSet<i32>>::with_capacity(10).unwrap();
+            scount
+                .estimate_distinct_elements_iter_with_rng(input_vec.into_iter(), &mut rng)
+                .unwrap();
+
+            for elem in scount.sample_distinct_elements() {
+                prop_assert!(seen.contains(elem));
+            }
+            prop_assert_eq!(
+                scount.sample_inclusion_probability(),
+                1.0 / scount.sampling_round as f64
+            );
+        }
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn const_capacity_matches_heap_capacity() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let input_vec = (0..1000).map(|_| rng.gen_range(0..15)).collect_vec();
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut heap_scount = StreamCountEstimator::<Vec<i32>>::with_capacity(10).unwrap();
+        let expected = heap_scount
+            .estimate_distinct_elements_iter_with_rng(input_vec.clone().into_iter(), &mut rng)
+            .unwrap();
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut scount = StreamCountEstimator::<heapless::Vec<i32, 10>>::with_const_capacity()
+            .unwrap();
+        let count = scount
+            .estimate_distinct_elements_iter_with_rng(input_vec.into_iter(), &mut rng)
+            .unwrap();
+
+        assert_eq!(count, expected);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn checkpoint_resume_matches_uninterrupted_run() {
+        let input_vec = (0..1000).map(|n| n % 17).collect_vec();
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut uninterrupted = StreamCountEstimator::<HashSet<i32>>::with_capacity(20).unwrap();
+        let expected = uninterrupted
+            .estimate_distinct_elements_iter_with_rng(input_vec.iter().copied(), &mut rng)
+            .unwrap();
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut scount = StreamCountEstimator::<HashSet<i32>>::with_capacity(20).unwrap();
+        let (first_half, second_half) = input_vec.split_at(400);
+        scount
+            .estimate_distinct_elements_iter_with_rng(first_half.iter().copied(), &mut rng)
+            .unwrap();
+
+        let snapshot = serde_json::to_string(&scount).unwrap();
+        let mut resumed: StreamCountEstimator<HashSet<i32>> =
+            serde_json::from_str(&snapshot).unwrap();
+        let count = resumed
+            .estimate_distinct_elements_iter_with_rng(second_half.iter().copied(), &mut rng)
+            .unwrap();
+
+        assert_eq!(count, expected);
+    }
 }
@@ -1,5 +1,19 @@
+#[cfg(feature = "std")]
 use std::{collections::HashSet, hash::Hash};
 
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+extern crate alloc;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+
+/// Bounds needed to (de)serialize an [`ElementSet`] under the `serde`
+/// feature. Blanket-implemented for every type that already satisfies them.
+#[cfg(feature = "serde")]
+pub trait Serializable: serde::Serialize + for<'de> serde::Deserialize<'de> {}
+
+#[cfg(feature = "serde")]
+impl<T> Serializable for T where T: serde::Serialize + for<'de> serde::Deserialize<'de> {}
+
 /// The ElementSet trait defines the operations needed
 /// for the stream count algorithm to operate (a subset
 /// of the operations availabel on hash sets).
@@ -24,6 +38,7 @@ pub trait ElementSet {
     fn iter(&self) -> impl Iterator<Item = &Self::Element>;
 }
 
+#[cfg(feature = "std")]
 impl<T> ElementSet for HashSet<T>
 where
     T: Eq + Hash,
@@ -57,6 +72,7 @@ where
 
 /// This implementation is very inefficient and should
 /// only used for snapshot testing.
+#[cfg(any(feature = "std", feature = "alloc"))]
 impl<T> ElementSet for Vec<T>
 where
     T: Eq,
@@ -93,6 +109,55 @@ where
     }
 }
 
+/// A fixed, stack-allocated `ElementSet` whose capacity is a const generic
+/// `N`, for allocation-free `no_std` targets (e.g. microcontrollers) where
+/// `HashSet`/`Vec` are unavailable. Backed by [`heapless::Vec`], mirroring
+/// the `Vec<T>` implementation above but without heap allocation.
+#[cfg(feature = "heapless")]
+impl<T, const N: usize> ElementSet for heapless::Vec<T, N>
+where
+    T: Eq,
+{
+    type Element = T;
+
+    /// `capacity` must not exceed the const generic `N`; callers that need
+    /// a specific capacity should size `N` accordingly, e.g. via
+    /// [`StreamCountEstimator::with_const_capacity`](crate::distinct::StreamCountEstimator::with_const_capacity).
+    fn with_capacity(capacity: usize) -> Self {
+        debug_assert!(
+            capacity <= N,
+            "requested capacity {capacity} exceeds the const capacity {N}"
+        );
+        Self::new()
+    }
+
+    /// When the set already holds `N` elements, `elem` is not inserted.
+    fn insert(&mut self, elem: Self::Element) {
+        if !self.contains(&elem) {
+            let _ = self.push(elem);
+        }
+    }
+
+    fn contains(&self, elem: &Self::Element) -> bool {
+        self.as_slice().iter().any(|val| val == elem)
+    }
+
+    fn remove(&mut self, elem: &Self::Element) {
+        let Some(pos) = self.as_slice().iter().position(|val| val == elem) else {
+            return;
+        };
+        heapless::Vec::remove(self, pos);
+    }
+
+    fn len(&self) -> usize {
+        heapless::Vec::len(self)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &T> {
+        self.as_slice().iter()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::marker::PhantomData;
@@ -1,4 +1,12 @@
-use std::{error::Error, fmt::Display};
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+use std::{error::Error, fmt::Display, string::String};
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(not(feature = "std"))]
+use core::fmt::Display;
 
 #[derive(Debug)]
 pub enum CountError {
@@ -6,10 +14,11 @@ pub enum CountError {
     Message(String),
 }
 
+#[cfg(feature = "std")]
 impl Error for CountError {}
 
 impl Display for CountError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         writeln!(f, "CountError(")?;
         match self {
             CountError::WrongInitialization(msg) => {
@@ -0,0 +1,10 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+pub mod distinct;
+pub mod elementset;
+pub mod error;
+
+pub use distinct::StreamCountEstimator;